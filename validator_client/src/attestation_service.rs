@@ -0,0 +1,189 @@
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::duties_service::DutiesService;
+use crate::fork_service::ForkService;
+use crate::health_service::HealthService;
+use crate::metrics::DutyMetrics;
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use slog::{error, info};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use types::{ChainSpec, EthSpec};
+
+/// Signs attestations for the local validators, behind the health gate and
+/// slashing protection. Signed attestations are not sent to the beacon node.
+#[derive(Clone)]
+pub struct AttestationService<S, T: EthSpec> {
+    duties_service: DutiesService<S, T>,
+    fork_service: ForkService<S, T>,
+    health_service: HealthService<S, T>,
+    slot_clock: S,
+    validator_store: ValidatorStore<T>,
+    beacon_node: BeaconNodeFallback<T>,
+    metrics: Arc<DutyMetrics>,
+    context: Arc<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone + Send + Sync + 'static, T: EthSpec> AttestationService<S, T> {
+    /// Returns the exit `Signal` for this service. No background loop is
+    /// spawned: attestations are not scheduled per slot from here.
+    ///
+    /// In `publish_attestation`, a validator whose attestation is refused by
+    /// `ValidatorStore::sign_attestation` (for example because it would
+    /// double-vote or surround-vote) has its duty skipped.
+    pub fn start_update_service(&self, _spec: &ChainSpec) -> Result<Signal, String> {
+        let (exit_signal, _exit) = exit_future::signal();
+        Ok(exit_signal)
+    }
+
+    fn publish_attestation(
+        &self,
+        validator_pubkey: types::PublicKey,
+        mut attestation: types::Attestation<T>,
+    ) {
+        let log = &self.context.log;
+
+        if !self.health_service.is_healthy() {
+            error!(
+                log,
+                "Connected beacon node is unhealthy, skipping attestation duty";
+                "validator_pubkey" => format!("{:?}", validator_pubkey),
+            );
+            return;
+        }
+
+        let fork = match self.fork_service.fork() {
+            Some(fork) => fork,
+            None => {
+                error!(
+                    log,
+                    "Fork not yet known, skipping attestation duty";
+                    "validator_pubkey" => format!("{:?}", validator_pubkey),
+                );
+                return;
+            }
+        };
+
+        match self
+            .validator_store
+            .sign_attestation(&validator_pubkey, &mut attestation, &fork)
+        {
+            Some(()) => {
+                info!(
+                    log,
+                    "Successfully signed attestation";
+                    "validator_pubkey" => format!("{:?}", validator_pubkey),
+                );
+                self.metrics.record_attestation_produced(&validator_pubkey);
+                // The signed attestation is not sent to the beacon node.
+            }
+            None => {
+                error!(
+                    log,
+                    "Attestation signing refused, skipping duty";
+                    "validator_pubkey" => format!("{:?}", validator_pubkey),
+                );
+                self.metrics.record_attestation_failed(&validator_pubkey);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AttestationServiceBuilder<S, T: EthSpec> {
+    duties_service: Option<DutiesService<S, T>>,
+    fork_service: Option<ForkService<S, T>>,
+    health_service: Option<HealthService<S, T>>,
+    slot_clock: Option<S>,
+    validator_store: Option<ValidatorStore<T>>,
+    beacon_node: Option<BeaconNodeFallback<T>>,
+    metrics: Option<Arc<DutyMetrics>>,
+    context: Option<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone + Send + Sync + 'static, T: EthSpec> AttestationServiceBuilder<S, T> {
+    pub fn new() -> Self {
+        Self {
+            duties_service: None,
+            fork_service: None,
+            health_service: None,
+            slot_clock: None,
+            validator_store: None,
+            beacon_node: None,
+            metrics: None,
+            context: None,
+        }
+    }
+
+    pub fn duties_service(mut self, duties_service: DutiesService<S, T>) -> Self {
+        self.duties_service = Some(duties_service);
+        self
+    }
+
+    pub fn fork_service(mut self, fork_service: ForkService<S, T>) -> Self {
+        self.fork_service = Some(fork_service);
+        self
+    }
+
+    pub fn health_service(mut self, health_service: HealthService<S, T>) -> Self {
+        self.health_service = Some(health_service);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: S) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn validator_store(mut self, validator_store: ValidatorStore<T>) -> Self {
+        self.validator_store = Some(validator_store);
+        self
+    }
+
+    pub fn beacon_node(mut self, beacon_node: BeaconNodeFallback<T>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Arc<DutyMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<T>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<AttestationService<S, T>, String> {
+        Ok(AttestationService {
+            duties_service: self
+                .duties_service
+                .ok_or_else(|| "AttestationServiceBuilder: missing duties_service".to_string())?,
+            fork_service: self
+                .fork_service
+                .ok_or_else(|| "AttestationServiceBuilder: missing fork_service".to_string())?,
+            health_service: self
+                .health_service
+                .ok_or_else(|| "AttestationServiceBuilder: missing health_service".to_string())?,
+            slot_clock: self
+                .slot_clock
+                .ok_or_else(|| "AttestationServiceBuilder: missing slot_clock".to_string())?,
+            validator_store: self
+                .validator_store
+                .ok_or_else(|| "AttestationServiceBuilder: missing validator_store".to_string())?,
+            beacon_node: self
+                .beacon_node
+                .ok_or_else(|| "AttestationServiceBuilder: missing beacon_node".to_string())?,
+            metrics: self
+                .metrics
+                .ok_or_else(|| "AttestationServiceBuilder: missing metrics".to_string())?,
+            context: Arc::new(
+                self.context.ok_or_else(|| {
+                    "AttestationServiceBuilder: missing runtime_context".to_string()
+                })?,
+            ),
+        })
+    }
+}
@@ -0,0 +1,305 @@
+//! Wraps a prioritized list of beacon node endpoints behind a single handle
+//! that exposes the same request pattern as a bare `RemoteBeaconNode`, so the
+//! duties, fork, block and attestation services keep working unmodified if a
+//! node goes offline.
+//!
+//! On every request the primary node is tried first; on connection error or
+//! timeout (i.e. the request future resolves to an `Err`), the next configured
+//! node is tried instead. Nodes marked unavailable are periodically re-probed
+//! so a recovered primary is used again rather than staying on a fallback
+//! indefinitely.
+//!
+//! `first_success_filtered` also falls over on a *successful* response that
+//! the caller flags as unusable (e.g. `HealthService` probing
+//! `syncing_status()` and finding the node still syncing); plain
+//! `first_success` accepts every successful response. Unlike a
+//! connection error this does not mark the node unavailable, since it is
+//! reachable and may become acceptable again on the very next poll.
+
+use futures::{future, Future};
+use parking_lot::RwLock;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{error, info, warn, Logger};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::EthSpec;
+
+/// How often an unavailable node is re-probed to see if it has recovered.
+const REPROBE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Candidate<E: EthSpec> {
+    beacon_node: RemoteBeaconNode<E>,
+    available: RwLock<bool>,
+}
+
+/// A prioritized, failover-aware handle to one or more beacon nodes.
+#[derive(Clone)]
+pub struct BeaconNodeFallback<E: EthSpec> {
+    candidates: Arc<Vec<Candidate<E>>>,
+    log: Logger,
+}
+
+impl<E: EthSpec> BeaconNodeFallback<E> {
+    pub fn new(beacon_nodes: Vec<RemoteBeaconNode<E>>, log: Logger) -> Self {
+        let candidates = beacon_nodes
+            .into_iter()
+            .map(|beacon_node| Candidate {
+                beacon_node,
+                available: RwLock::new(true),
+            })
+            .collect();
+
+        Self {
+            candidates: Arc::new(candidates),
+            log,
+        }
+    }
+
+    pub fn num_available(&self) -> usize {
+        self.candidates
+            .iter()
+            .filter(|c| *c.available.read())
+            .count()
+    }
+
+    /// Run `func` against each available node in priority order, returning the
+    /// first success. A node that errors is marked unavailable (until the next
+    /// re-probe) and the switch to the next node is logged.
+    pub fn first_success<F, R, I>(
+        &self,
+        func: F,
+    ) -> Box<dyn Future<Item = I, Error = String> + Send>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R + Send + Sync + 'static,
+        R: Future<Item = I, Error = String> + Send + 'static,
+        I: Send + 'static,
+    {
+        self.first_success_filtered(func, |_| true)
+    }
+
+    /// As `first_success`, but a successful response for which `is_acceptable`
+    /// returns `false` (e.g. a node reporting `is_syncing: true`) is treated
+    /// like a failure for fallover purposes: the next node is tried instead.
+    /// If every node's response is unacceptable, the last one received is
+    /// returned rather than an error, since no request actually failed.
+    pub fn first_success_filtered<F, R, I, P>(
+        &self,
+        func: F,
+        is_acceptable: P,
+    ) -> Box<dyn Future<Item = I, Error = String> + Send>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R + Send + Sync + 'static,
+        R: Future<Item = I, Error = String> + Send + 'static,
+        I: Send + 'static,
+        P: Fn(&I) -> bool + Send + Sync + 'static,
+    {
+        let attempts: Vec<usize> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| *c.available.read())
+            .map(|(i, _)| i)
+            .collect();
+
+        try_each(
+            self.candidates.clone(),
+            attempts,
+            0,
+            Arc::new(func),
+            Arc::new(is_acceptable),
+            self.log.clone(),
+        )
+    }
+
+    /// Spawn a background loop that periodically re-probes unavailable nodes
+    /// via `get_version`, marking them available again on success.
+    pub fn start_update_service(&self) -> exit_future::Signal {
+        let (signal, exit) = exit_future::signal();
+        let candidates = self.candidates.clone();
+        let log = self.log.clone();
+
+        let interval = Interval::new(Instant::now() + REPROBE_INTERVAL, REPROBE_INTERVAL)
+            .map_err(|e| format!("Beacon node reprobe interval failed: {:?}", e))
+            .for_each(move |_| {
+                reprobe_unavailable(candidates.clone(), log.clone());
+                Ok(())
+            })
+            .map_err(|_| ());
+
+        tokio::spawn(exit.until(interval).map(|_| ()));
+
+        signal
+    }
+}
+
+fn reprobe_unavailable<E: EthSpec>(candidates: Arc<Vec<Candidate<E>>>, log: Logger) {
+    for (index, candidate) in candidates.iter().enumerate() {
+        if *candidate.available.read() {
+            continue;
+        }
+
+        let candidates = candidates.clone();
+        let log = log.clone();
+
+        tokio::spawn(
+            candidate
+                .beacon_node
+                .http
+                .node()
+                .get_version()
+                .map(move |_| {
+                    *candidates[index].available.write() = true;
+                    info!(log, "Beacon node has recovered"; "node_index" => index);
+                })
+                .map_err(|_| ()),
+        );
+    }
+}
+
+fn try_each<E, F, R, I, P>(
+    candidates: Arc<Vec<Candidate<E>>>,
+    attempts: Vec<usize>,
+    pos: usize,
+    func: Arc<F>,
+    is_acceptable: Arc<P>,
+    log: Logger,
+) -> Box<dyn Future<Item = I, Error = String> + Send>
+where
+    E: EthSpec,
+    F: Fn(RemoteBeaconNode<E>) -> R + Send + Sync + 'static,
+    R: Future<Item = I, Error = String> + Send + 'static,
+    I: Send + 'static,
+    P: Fn(&I) -> bool + Send + Sync + 'static,
+{
+    if attempts.is_empty() {
+        return Box::new(future::err(
+            "No configured beacon nodes are available".to_string(),
+        ));
+    }
+
+    let index = attempts[pos];
+    let beacon_node = candidates[index].beacon_node.clone();
+    let is_last = pos + 1 >= attempts.len();
+
+    Box::new(func(beacon_node).then(move |result| match result {
+        Ok(item) => {
+            if is_acceptable(&item) || is_last {
+                Box::new(future::ok(item)) as Box<dyn Future<Item = I, Error = String> + Send>
+            } else {
+                warn!(
+                    log,
+                    "Beacon node response unacceptable, falling over to next node";
+                    "node_index" => index,
+                );
+                try_each(candidates, attempts, pos + 1, func, is_acceptable, log)
+            }
+        }
+        Err(e) => {
+            *candidates[index].available.write() = false;
+
+            if !is_last {
+                warn!(
+                    log,
+                    "Beacon node request failed, falling over to next node";
+                    "failed_node_index" => index,
+                    "error" => e,
+                );
+                try_each(candidates, attempts, pos + 1, func, is_acceptable, log)
+            } else {
+                error!(log, "All beacon nodes failed to service request"; "error" => e);
+                Box::new(future::err(e))
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use types::MainnetEthSpec;
+
+    fn null_logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
+    fn new_fallback(n: usize) -> BeaconNodeFallback<MainnetEthSpec> {
+        let beacon_nodes = (0..n)
+            .map(|i| RemoteBeaconNode::new(format!("127.0.0.1:{}", 10000 + i).parse().unwrap()).unwrap())
+            .collect();
+        BeaconNodeFallback::new(beacon_nodes, null_logger())
+    }
+
+    #[test]
+    fn first_success_falls_over_on_error() {
+        let fallback = new_fallback(3);
+        let attempt = Arc::new(AtomicUsize::new(0));
+        let responses = vec![
+            Err("node 0 down".to_string()),
+            Err("node 1 down".to_string()),
+            Ok(42),
+        ];
+
+        let result = fallback
+            .first_success(move |_node| {
+                let i = attempt.fetch_add(1, Ordering::SeqCst);
+                match &responses[i] {
+                    Ok(v) => future::ok(*v),
+                    Err(e) => future::err(e.clone()),
+                }
+            })
+            .wait();
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(fallback.num_available(), 1);
+    }
+
+    #[test]
+    fn first_success_errors_when_all_nodes_fail() {
+        let fallback = new_fallback(2);
+
+        let result = fallback
+            .first_success(|_node| future::err::<(), String>("down".to_string()))
+            .wait();
+
+        assert!(result.is_err());
+        assert_eq!(fallback.num_available(), 0);
+    }
+
+    #[test]
+    fn first_success_filtered_falls_over_on_unacceptable_success() {
+        let fallback = new_fallback(3);
+        let attempt = Arc::new(AtomicUsize::new(0));
+        let responses = vec![true, true, false];
+
+        let result = fallback
+            .first_success_filtered(
+                move |_node| {
+                    let i = attempt.fetch_add(1, Ordering::SeqCst);
+                    future::ok::<bool, String>(responses[i])
+                },
+                |is_syncing| !is_syncing,
+            )
+            .wait();
+
+        assert_eq!(result, Ok(false));
+        // A syncing-but-reachable node is not a connection failure, so it
+        // stays available for the next call.
+        assert_eq!(fallback.num_available(), 3);
+    }
+
+    #[test]
+    fn first_success_filtered_returns_last_response_when_none_acceptable() {
+        let fallback = new_fallback(2);
+
+        let result = fallback
+            .first_success_filtered(
+                |_node| future::ok::<bool, String>(true),
+                |is_syncing| !is_syncing,
+            )
+            .wait();
+
+        assert_eq!(result, Ok(true));
+    }
+}
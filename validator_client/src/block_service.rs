@@ -0,0 +1,181 @@
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::duties_service::DutiesService;
+use crate::fork_service::ForkService;
+use crate::health_service::HealthService;
+use crate::metrics::DutyMetrics;
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use slog::{error, info};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use types::{ChainSpec, EthSpec};
+
+/// Signs blocks for the local validators, behind the health gate and slashing
+/// protection. Signed blocks are not sent to the beacon node.
+#[derive(Clone)]
+pub struct BlockService<S, T: EthSpec> {
+    duties_service: DutiesService<S, T>,
+    fork_service: ForkService<S, T>,
+    health_service: HealthService<S, T>,
+    slot_clock: S,
+    validator_store: ValidatorStore<T>,
+    beacon_node: BeaconNodeFallback<T>,
+    metrics: Arc<DutyMetrics>,
+    context: Arc<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone + Send + Sync + 'static, T: EthSpec> BlockService<S, T> {
+    /// Returns the exit `Signal` for this service. No background loop is
+    /// spawned: blocks are not scheduled per slot from here.
+    ///
+    /// In `publish_block`, a validator whose block is refused by
+    /// `ValidatorStore::sign_block` (for example because it would be
+    /// slashable) has its duty skipped.
+    pub fn start_update_service(&self, _spec: &ChainSpec) -> Result<Signal, String> {
+        let (exit_signal, _exit) = exit_future::signal();
+        Ok(exit_signal)
+    }
+
+    fn publish_block(&self, validator_pubkey: types::PublicKey, block: types::BeaconBlock<T>) {
+        let log = &self.context.log;
+
+        if !self.health_service.is_healthy() {
+            error!(
+                log,
+                "Connected beacon node is unhealthy, skipping block duty";
+                "validator_pubkey" => format!("{:?}", validator_pubkey),
+            );
+            return;
+        }
+
+        let fork = match self.fork_service.fork() {
+            Some(fork) => fork,
+            None => {
+                error!(
+                    log,
+                    "Fork not yet known, skipping block duty";
+                    "validator_pubkey" => format!("{:?}", validator_pubkey),
+                );
+                return;
+            }
+        };
+
+        match self.validator_store.sign_block(&validator_pubkey, block, &fork) {
+            Some(_signed_block) => {
+                info!(
+                    log,
+                    "Successfully signed block";
+                    "validator_pubkey" => format!("{:?}", validator_pubkey),
+                );
+                self.metrics.record_block_produced(&validator_pubkey);
+                // The signed block is not sent to the beacon node.
+            }
+            None => {
+                error!(
+                    log,
+                    "Block signing refused, skipping duty";
+                    "validator_pubkey" => format!("{:?}", validator_pubkey),
+                );
+                self.metrics.record_block_failed(&validator_pubkey);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BlockServiceBuilder<S, T: EthSpec> {
+    duties_service: Option<DutiesService<S, T>>,
+    fork_service: Option<ForkService<S, T>>,
+    health_service: Option<HealthService<S, T>>,
+    slot_clock: Option<S>,
+    validator_store: Option<ValidatorStore<T>>,
+    beacon_node: Option<BeaconNodeFallback<T>>,
+    metrics: Option<Arc<DutyMetrics>>,
+    context: Option<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone + Send + Sync + 'static, T: EthSpec> BlockServiceBuilder<S, T> {
+    pub fn new() -> Self {
+        Self {
+            duties_service: None,
+            fork_service: None,
+            health_service: None,
+            slot_clock: None,
+            validator_store: None,
+            beacon_node: None,
+            metrics: None,
+            context: None,
+        }
+    }
+
+    pub fn duties_service(mut self, duties_service: DutiesService<S, T>) -> Self {
+        self.duties_service = Some(duties_service);
+        self
+    }
+
+    pub fn fork_service(mut self, fork_service: ForkService<S, T>) -> Self {
+        self.fork_service = Some(fork_service);
+        self
+    }
+
+    pub fn health_service(mut self, health_service: HealthService<S, T>) -> Self {
+        self.health_service = Some(health_service);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: S) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn validator_store(mut self, validator_store: ValidatorStore<T>) -> Self {
+        self.validator_store = Some(validator_store);
+        self
+    }
+
+    pub fn beacon_node(mut self, beacon_node: BeaconNodeFallback<T>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Arc<DutyMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<T>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<BlockService<S, T>, String> {
+        Ok(BlockService {
+            duties_service: self
+                .duties_service
+                .ok_or_else(|| "BlockServiceBuilder: missing duties_service".to_string())?,
+            fork_service: self
+                .fork_service
+                .ok_or_else(|| "BlockServiceBuilder: missing fork_service".to_string())?,
+            health_service: self
+                .health_service
+                .ok_or_else(|| "BlockServiceBuilder: missing health_service".to_string())?,
+            slot_clock: self
+                .slot_clock
+                .ok_or_else(|| "BlockServiceBuilder: missing slot_clock".to_string())?,
+            validator_store: self
+                .validator_store
+                .ok_or_else(|| "BlockServiceBuilder: missing validator_store".to_string())?,
+            beacon_node: self
+                .beacon_node
+                .ok_or_else(|| "BlockServiceBuilder: missing beacon_node".to_string())?,
+            metrics: self
+                .metrics
+                .ok_or_else(|| "BlockServiceBuilder: missing metrics".to_string())?,
+            context: Arc::new(
+                self.context
+                    .ok_or_else(|| "BlockServiceBuilder: missing runtime_context".to_string())?,
+            ),
+        })
+    }
+}
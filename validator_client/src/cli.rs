@@ -0,0 +1,88 @@
+use clap::{App, Arg, SubCommand};
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("validator_client")
+        .visible_aliases(&["v", "vc", "validator"])
+        .about("Runs a validator client that connects to a beacon node.")
+        .arg(
+            Arg::with_name("datadir")
+                .long("datadir")
+                .value_name("DIR")
+                .help("Data directory for storing validator keypairs and slashing protection.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("server")
+                .long("server")
+                .value_name("NETWORK_ADDRESS")
+                .help("Address to connect to beacon node.")
+                .default_value("localhost")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("server-http-port")
+                .long("server-http-port")
+                .value_name("PORT")
+                .help("HTTP port to connect to beacon node.")
+                .default_value("5052")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("beacon-nodes")
+                .long("beacon-nodes")
+                .value_name("NETWORK_ADDRESSES")
+                .help(
+                    "Comma-separated list of `host:port` beacon node endpoints, tried in \
+                     order with automatic failover. Overrides --server/--server-http-port.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("import-slashing-protection")
+                .long("import-slashing-protection")
+                .value_name("FILE")
+                .help(
+                    "Import a slashing-protection interchange file before starting, so \
+                     validators migrating from another client are not at risk of \
+                     signing a duty they have already performed.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("health-tolerance-slots")
+                .long("health-tolerance-slots")
+                .value_name("SLOTS")
+                .help(
+                    "The number of slots the connected beacon node's head is allowed to lag \
+                     behind the wall clock before duties are refused.",
+                )
+                .default_value("2")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-api")
+                .long("http-api")
+                .help(
+                    "Starts a local HTTP API exposing Prometheus-style duty metrics, beacon \
+                     node health, and validator counts. Serves no authentication, so it should \
+                     only be bound to a loopback or otherwise trusted address.",
+                )
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("http-api-listen-addr")
+                .long("http-api-listen-addr")
+                .value_name("ADDRESS")
+                .help("Address for the HTTP API server to listen on.")
+                .default_value("127.0.0.1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-api-port")
+                .long("http-api-port")
+                .value_name("PORT")
+                .help("Port for the HTTP API server to listen on.")
+                .default_value("5064")
+                .takes_value(true),
+        )
+}
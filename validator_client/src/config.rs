@@ -0,0 +1,135 @@
+use clap::ArgMatches;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::ops::Range;
+use std::path::PathBuf;
+
+pub const DEFAULT_DATA_DIR: &str = ".lighthouse/validators";
+
+#[derive(Clone)]
+pub enum KeySource {
+    /// Load validators from `data_dir`.
+    Disk,
+    /// Generate ephemeral, insecure keypairs for testing purposes.
+    TestingKeypairRange(Range<usize>),
+}
+
+#[derive(Clone)]
+pub struct Config {
+    pub data_dir: PathBuf,
+    /// An ordered list of `server:port` beacon node endpoints. The first
+    /// reachable node is used; the rest serve as fallbacks.
+    pub beacon_nodes: Vec<String>,
+    pub key_source: KeySource,
+    /// Path to a slashing-protection interchange file to import on startup, for
+    /// validators migrating their signing history from another client.
+    pub import_slashing_protection: Option<PathBuf>,
+    /// The number of slots the connected beacon node's head is allowed to lag
+    /// behind the wall clock before it is considered unhealthy and duties are
+    /// refused.
+    pub health_tolerance_slots: u64,
+    /// Whether the local HTTP/metrics API should be started alongside the
+    /// validator client's other services.
+    pub http_api_enabled: bool,
+    /// The address the HTTP API listens on. Defaults to loopback-only, since
+    /// the API serves operational state with no authentication.
+    pub http_api_listen_addr: IpAddr,
+    pub http_api_port: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from(DEFAULT_DATA_DIR),
+            beacon_nodes: vec!["localhost:5052".to_string()],
+            key_source: KeySource::Disk,
+            import_slashing_protection: None,
+            health_tolerance_slots: 2,
+            http_api_enabled: false,
+            http_api_listen_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            http_api_port: 5064,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_cli(cli_args: &ArgMatches) -> Result<Self, String> {
+        let mut config = Config::default();
+
+        if let Some(datadir) = cli_args.value_of("datadir") {
+            config.data_dir = PathBuf::from(datadir);
+        }
+        fs::create_dir_all(&config.data_dir)
+            .map_err(|e| format!("Unable to create data dir: {:?}", e))?;
+
+        if let Some(server) = cli_args.value_of("server") {
+            let server_http_port = cli_args.value_of("server-http-port").unwrap_or("5052");
+            config.beacon_nodes = vec![format!("{}:{}", server, server_http_port)];
+        }
+
+        if let Some(beacon_nodes) = cli_args.value_of("beacon-nodes") {
+            config.beacon_nodes = beacon_nodes
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+        }
+
+        if let Some(path) = cli_args.value_of("import-slashing-protection") {
+            config.import_slashing_protection = Some(PathBuf::from(path));
+        }
+
+        if let Some(tolerance) = cli_args.value_of("health-tolerance-slots") {
+            config.health_tolerance_slots = tolerance
+                .parse()
+                .map_err(|e| format!("Unable to parse health-tolerance-slots: {:?}", e))?;
+        }
+
+        if cli_args.is_present("http-api") {
+            config.http_api_enabled = true;
+        }
+
+        if let Some(addr) = cli_args.value_of("http-api-listen-addr") {
+            config.http_api_listen_addr = addr
+                .parse()
+                .map_err(|e| format!("Unable to parse http-api-listen-addr: {:?}", e))?;
+        }
+
+        if let Some(port) = cli_args.value_of("http-api-port") {
+            config.http_api_port = port
+                .parse()
+                .map_err(|e| format!("Unable to parse http-api-port: {:?}", e))?;
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::cli_app;
+
+    fn parse(args: &[&str]) -> Config {
+        let matches = cli_app()
+            .get_matches_from_safe(
+                std::iter::once("validator_client").chain(args.iter().copied()),
+            )
+            .expect("args should parse");
+        Config::from_cli(&matches).expect("config should build")
+    }
+
+    #[test]
+    fn beacon_nodes_overrides_default_server() {
+        let config = parse(&["--beacon-nodes", "host-a:1000,host-b:2000"]);
+        assert_eq!(
+            config.beacon_nodes,
+            vec!["host-a:1000".to_string(), "host-b:2000".to_string()]
+        );
+    }
+
+    #[test]
+    fn server_alone_still_works() {
+        let config = parse(&["--server", "example.com", "--server-http-port", "6000"]);
+        assert_eq!(config.beacon_nodes, vec!["example.com:6000".to_string()]);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use types::{ChainSpec, EthSpec};
+
+/// Holds the slot clock, validator store and beacon node handle for duty
+/// tracking. Duties are not fetched from the beacon node.
+#[derive(Clone)]
+pub struct DutiesService<S, T> {
+    slot_clock: S,
+    validator_store: ValidatorStore<T>,
+    beacon_node: BeaconNodeFallback<T>,
+    context: Arc<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone, T: EthSpec> DutiesService<S, T> {
+    /// Returns the exit `Signal` for this service. No background loop is
+    /// spawned.
+    pub fn start_update_service(&self, _spec: &ChainSpec) -> Result<Signal, String> {
+        let (exit_signal, _exit) = exit_future::signal();
+        Ok(exit_signal)
+    }
+}
+
+#[derive(Default)]
+pub struct DutiesServiceBuilder<S, T> {
+    slot_clock: Option<S>,
+    validator_store: Option<ValidatorStore<T>>,
+    beacon_node: Option<BeaconNodeFallback<T>>,
+    context: Option<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone, T: EthSpec> DutiesServiceBuilder<S, T> {
+    pub fn new() -> Self {
+        Self {
+            slot_clock: None,
+            validator_store: None,
+            beacon_node: None,
+            context: None,
+        }
+    }
+
+    pub fn slot_clock(mut self, slot_clock: S) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn validator_store(mut self, validator_store: ValidatorStore<T>) -> Self {
+        self.validator_store = Some(validator_store);
+        self
+    }
+
+    pub fn beacon_node(mut self, beacon_node: BeaconNodeFallback<T>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<T>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<DutiesService<S, T>, String> {
+        Ok(DutiesService {
+            slot_clock: self
+                .slot_clock
+                .ok_or_else(|| "DutiesServiceBuilder: missing slot_clock".to_string())?,
+            validator_store: self
+                .validator_store
+                .ok_or_else(|| "DutiesServiceBuilder: missing validator_store".to_string())?,
+            beacon_node: self
+                .beacon_node
+                .ok_or_else(|| "DutiesServiceBuilder: missing beacon_node".to_string())?,
+            context: Arc::new(
+                self.context
+                    .ok_or_else(|| "DutiesServiceBuilder: missing runtime_context".to_string())?,
+            ),
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use parking_lot::RwLock;
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use types::{ChainSpec, EthSpec, Fork};
+
+/// Holds the current `Fork` for the signing services' domain computations.
+/// The fork is not fetched from the beacon node and nothing writes it, so
+/// `fork()` returns `None`.
+#[derive(Clone)]
+pub struct ForkService<S, T> {
+    fork: Arc<RwLock<Option<Fork>>>,
+    slot_clock: S,
+    beacon_node: BeaconNodeFallback<T>,
+    context: Arc<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone, T: EthSpec> ForkService<S, T> {
+    pub fn fork(&self) -> Option<Fork> {
+        self.fork.read().clone()
+    }
+
+    /// Returns the exit `Signal` for this service. No background loop is
+    /// spawned.
+    pub fn start_update_service(&self, _spec: &ChainSpec) -> Result<Signal, String> {
+        let (exit_signal, _exit) = exit_future::signal();
+        Ok(exit_signal)
+    }
+}
+
+#[derive(Default)]
+pub struct ForkServiceBuilder<S, T> {
+    slot_clock: Option<S>,
+    beacon_node: Option<BeaconNodeFallback<T>>,
+    context: Option<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone, T: EthSpec> ForkServiceBuilder<S, T> {
+    pub fn new() -> Self {
+        Self {
+            slot_clock: None,
+            beacon_node: None,
+            context: None,
+        }
+    }
+
+    pub fn slot_clock(mut self, slot_clock: S) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn beacon_node(mut self, beacon_node: BeaconNodeFallback<T>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<T>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<ForkService<S, T>, String> {
+        Ok(ForkService {
+            fork: Arc::new(RwLock::new(None)),
+            slot_clock: self
+                .slot_clock
+                .ok_or_else(|| "ForkServiceBuilder: missing slot_clock".to_string())?,
+            beacon_node: self
+                .beacon_node
+                .ok_or_else(|| "ForkServiceBuilder: missing beacon_node".to_string())?,
+            context: Arc::new(
+                self.context
+                    .ok_or_else(|| "ForkServiceBuilder: missing runtime_context".to_string())?,
+            ),
+        })
+    }
+}
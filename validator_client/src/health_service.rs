@@ -0,0 +1,228 @@
+//! Periodically polls the connected beacon node's sync status so the signing
+//! services can refuse to produce duties against a node that is syncing or
+//! whose head has fallen behind the wall clock.
+
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::Future;
+use parking_lot::RwLock;
+use slog::{info, warn};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::{ChainSpec, EthSpec, Slot};
+
+/// How often the beacon node's sync status is polled.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(6);
+
+/// The most recently observed state of the connected beacon node.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeHealth {
+    pub is_syncing: bool,
+    pub head_slot: Slot,
+    pub wall_clock_slot: Slot,
+}
+
+impl NodeHealth {
+    /// True if the node is not syncing and its head is within `tolerance_slots`
+    /// of the current wall-clock slot.
+    pub fn is_healthy(&self, tolerance_slots: u64) -> bool {
+        !self.is_syncing
+            && self
+                .wall_clock_slot
+                .as_u64()
+                .saturating_sub(self.head_slot.as_u64())
+                <= tolerance_slots
+    }
+}
+
+#[derive(Clone)]
+pub struct HealthService<S, T: EthSpec> {
+    beacon_node: BeaconNodeFallback<T>,
+    slot_clock: S,
+    tolerance_slots: u64,
+    health: Arc<RwLock<Option<NodeHealth>>>,
+    context: Arc<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone + Send + Sync + 'static, T: EthSpec> HealthService<S, T> {
+    /// The most recently observed node health, or `None` before the first poll
+    /// and after a poll in which no beacon node returned a sync status.
+    pub fn health(&self) -> Option<NodeHealth> {
+        *self.health.read()
+    }
+
+    /// Whether the connected node is currently considered fit to sign duties
+    /// against. Defaults to unhealthy until the first successful poll.
+    pub fn is_healthy(&self) -> bool {
+        self.health()
+            .map(|health| health.is_healthy(self.tolerance_slots))
+            .unwrap_or(false)
+    }
+
+    /// Start the background loop that polls the beacon node's sync status
+    /// once every `HEALTH_CHECK_INTERVAL`, keyed off the same `SlotClock` used
+    /// for duties so that `wall_clock_slot` always reflects the current slot.
+    pub fn start_update_service(&self, _spec: &ChainSpec) -> Result<Signal, String> {
+        let (exit_signal, exit) = exit_future::signal();
+        let beacon_node = self.beacon_node.clone();
+        let slot_clock = self.slot_clock.clone();
+        let tolerance_slots = self.tolerance_slots;
+        let health = self.health.clone();
+        let log = self.context.log.clone();
+
+        let interval = Interval::new(
+            Instant::now() + HEALTH_CHECK_INTERVAL,
+            HEALTH_CHECK_INTERVAL,
+        )
+        .map_err(|e| format!("Health check interval failed: {:?}", e))
+        .for_each(move |_| {
+            let wall_clock_slot = match slot_clock.now() {
+                Some(slot) => slot,
+                None => return Ok(()),
+            };
+
+            let health = health.clone();
+            let log = log.clone();
+
+            tokio::spawn(
+                beacon_node
+                    .first_success_filtered(
+                        |beacon_node| beacon_node.http.node().syncing_status(),
+                        |status| !status.is_syncing,
+                    )
+                    .then(move |result| {
+                        match result {
+                            Ok(status) => {
+                                let new_health = NodeHealth {
+                                    is_syncing: status.is_syncing,
+                                    head_slot: status.head_slot,
+                                    wall_clock_slot,
+                                };
+
+                                if !new_health.is_healthy(tolerance_slots) {
+                                    warn!(
+                                        log,
+                                        "Connected beacon node is unhealthy";
+                                        "is_syncing" => new_health.is_syncing,
+                                        "head_slot" => new_health.head_slot.as_u64(),
+                                        "wall_clock_slot" => wall_clock_slot.as_u64(),
+                                    );
+                                } else {
+                                    info!(
+                                        log,
+                                        "Beacon node health";
+                                        "head_slot" => new_health.head_slot.as_u64(),
+                                        "wall_clock_slot" => wall_clock_slot.as_u64(),
+                                    );
+                                }
+
+                                *health.write() = Some(new_health);
+                            }
+                            Err(_) => *health.write() = None,
+                        };
+
+                        Ok(())
+                    }),
+            );
+
+            Ok(())
+        })
+        .map_err(|_: String| ());
+
+        tokio::spawn(exit.until(interval).map(|_| ()));
+
+        Ok(exit_signal)
+    }
+}
+
+#[derive(Default)]
+pub struct HealthServiceBuilder<S, T: EthSpec> {
+    beacon_node: Option<BeaconNodeFallback<T>>,
+    slot_clock: Option<S>,
+    tolerance_slots: Option<u64>,
+    context: Option<RuntimeContext<T>>,
+}
+
+impl<S: SlotClock + Clone, T: EthSpec> HealthServiceBuilder<S, T> {
+    pub fn new() -> Self {
+        Self {
+            beacon_node: None,
+            slot_clock: None,
+            tolerance_slots: None,
+            context: None,
+        }
+    }
+
+    pub fn beacon_node(mut self, beacon_node: BeaconNodeFallback<T>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: S) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn tolerance_slots(mut self, tolerance_slots: u64) -> Self {
+        self.tolerance_slots = Some(tolerance_slots);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<T>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<HealthService<S, T>, String> {
+        Ok(HealthService {
+            beacon_node: self
+                .beacon_node
+                .ok_or_else(|| "HealthServiceBuilder: missing beacon_node".to_string())?,
+            slot_clock: self
+                .slot_clock
+                .ok_or_else(|| "HealthServiceBuilder: missing slot_clock".to_string())?,
+            tolerance_slots: self.tolerance_slots.unwrap_or(2),
+            health: Arc::new(RwLock::new(None)),
+            context: Arc::new(
+                self.context
+                    .ok_or_else(|| "HealthServiceBuilder: missing runtime_context".to_string())?,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(head_slot: u64, wall_clock_slot: u64, is_syncing: bool) -> NodeHealth {
+        NodeHealth {
+            is_syncing,
+            head_slot: Slot::new(head_slot),
+            wall_clock_slot: Slot::new(wall_clock_slot),
+        }
+    }
+
+    #[test]
+    fn unhealthy_while_syncing_even_with_head_at_tip() {
+        assert!(!health(10, 10, true).is_healthy(2));
+    }
+
+    #[test]
+    fn healthy_at_exactly_the_tolerance_boundary() {
+        assert!(health(8, 10, false).is_healthy(2));
+    }
+
+    #[test]
+    fn unhealthy_one_slot_past_the_tolerance_boundary() {
+        assert!(!health(7, 10, false).is_healthy(2));
+    }
+
+    #[test]
+    fn healthy_when_head_is_ahead_of_wall_clock() {
+        assert!(health(11, 10, false).is_healthy(2));
+    }
+}
@@ -0,0 +1,186 @@
+//! An optional local HTTP server exposing operational state: Prometheus-style
+//! duty counters, connected beacon node health, and validator counts. Intended
+//! for scraping by monitoring, not for any privileged or mutating operation.
+
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::health_service::HealthService;
+use crate::metrics::DutyMetrics;
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use serde::Serialize;
+use slog::info;
+use slot_clock::{SlotClock, SystemTimeSlotClock};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use types::EthSpec;
+use warp::Filter;
+
+#[derive(Serialize)]
+struct HealthResponse {
+    is_healthy: bool,
+    is_syncing: Option<bool>,
+    head_slot: Option<u64>,
+    /// The current slot, read live from the `SlotClock` on every request
+    /// rather than cached from the last periodic health check.
+    current_slot: Option<u64>,
+    current_epoch: Option<u64>,
+    num_available_beacon_nodes: usize,
+}
+
+#[derive(Serialize)]
+struct ValidatorsResponse {
+    num_voting_validators: usize,
+}
+
+#[derive(Clone)]
+pub struct ApiService<T: EthSpec> {
+    listen_addr: SocketAddr,
+    metrics: Arc<DutyMetrics>,
+    health_service: HealthService<SystemTimeSlotClock, T>,
+    beacon_nodes: BeaconNodeFallback<T>,
+    validator_store: ValidatorStore<T>,
+    slot_clock: SystemTimeSlotClock,
+    context: Arc<RuntimeContext<T>>,
+}
+
+impl<T: EthSpec> ApiService<T> {
+    /// Bind the HTTP API and serve it until the returned `Signal` is dropped.
+    pub fn start_update_service(&self) -> Result<Signal, String> {
+        let (exit_signal, exit) = exit_future::signal();
+        let log = self.context.log.clone();
+
+        let metrics = self.metrics.clone();
+        let metrics_route = warp::path("metrics")
+            .and(warp::get2())
+            .map(move || metrics.render_prometheus());
+
+        let health_service = self.health_service.clone();
+        let beacon_nodes = self.beacon_nodes.clone();
+        let slot_clock = self.slot_clock.clone();
+        let health_route = warp::path("health").and(warp::get2()).map(move || {
+            let health = health_service.health();
+            let current_slot = slot_clock.now();
+            let current_epoch = current_slot.map(|slot| slot.epoch(T::slots_per_epoch()));
+
+            warp::reply::json(&HealthResponse {
+                is_healthy: health_service.is_healthy(),
+                is_syncing: health.map(|h| h.is_syncing),
+                head_slot: health.map(|h| h.head_slot.as_u64()),
+                current_slot: current_slot.map(|slot| slot.as_u64()),
+                current_epoch: current_epoch.map(|epoch| epoch.as_u64()),
+                num_available_beacon_nodes: beacon_nodes.num_available(),
+            })
+        });
+
+        let validator_store = self.validator_store.clone();
+        let validators_route = warp::path("validators").and(warp::get2()).map(move || {
+            warp::reply::json(&ValidatorsResponse {
+                num_voting_validators: validator_store.num_voting_validators(),
+            })
+        });
+
+        let routes = metrics_route.or(health_route).or(validators_route);
+
+        info!(
+            log,
+            "Starting HTTP API";
+            "listen_address" => format!("{}", self.listen_addr),
+        );
+
+        let (_addr, server) = warp::serve(routes)
+            .try_bind_with_graceful_shutdown(self.listen_addr, exit.map(|_| ()))
+            .map_err(|e| format!("Unable to bind HTTP API: {:?}", e))?;
+
+        tokio::spawn(server);
+
+        Ok(exit_signal)
+    }
+}
+
+#[derive(Default)]
+pub struct ApiServiceBuilder<T: EthSpec> {
+    listen_addr: Option<SocketAddr>,
+    metrics: Option<Arc<DutyMetrics>>,
+    health_service: Option<HealthService<SystemTimeSlotClock, T>>,
+    beacon_nodes: Option<BeaconNodeFallback<T>>,
+    validator_store: Option<ValidatorStore<T>>,
+    slot_clock: Option<SystemTimeSlotClock>,
+    context: Option<RuntimeContext<T>>,
+}
+
+impl<T: EthSpec> ApiServiceBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            listen_addr: None,
+            metrics: None,
+            health_service: None,
+            beacon_nodes: None,
+            validator_store: None,
+            slot_clock: None,
+            context: None,
+        }
+    }
+
+    pub fn listen_addr(mut self, listen_addr: SocketAddr) -> Self {
+        self.listen_addr = Some(listen_addr);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Arc<DutyMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn health_service(mut self, health_service: HealthService<SystemTimeSlotClock, T>) -> Self {
+        self.health_service = Some(health_service);
+        self
+    }
+
+    pub fn beacon_nodes(mut self, beacon_nodes: BeaconNodeFallback<T>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
+        self
+    }
+
+    pub fn validator_store(mut self, validator_store: ValidatorStore<T>) -> Self {
+        self.validator_store = Some(validator_store);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: SystemTimeSlotClock) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<T>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<ApiService<T>, String> {
+        Ok(ApiService {
+            listen_addr: self
+                .listen_addr
+                .ok_or_else(|| "ApiServiceBuilder: missing listen_addr".to_string())?,
+            metrics: self
+                .metrics
+                .ok_or_else(|| "ApiServiceBuilder: missing metrics".to_string())?,
+            health_service: self
+                .health_service
+                .ok_or_else(|| "ApiServiceBuilder: missing health_service".to_string())?,
+            beacon_nodes: self
+                .beacon_nodes
+                .ok_or_else(|| "ApiServiceBuilder: missing beacon_nodes".to_string())?,
+            validator_store: self
+                .validator_store
+                .ok_or_else(|| "ApiServiceBuilder: missing validator_store".to_string())?,
+            slot_clock: self
+                .slot_clock
+                .ok_or_else(|| "ApiServiceBuilder: missing slot_clock".to_string())?,
+            context: Arc::new(
+                self.context
+                    .ok_or_else(|| "ApiServiceBuilder: missing runtime_context".to_string())?,
+            ),
+        })
+    }
+}
@@ -1,9 +1,14 @@
 mod attestation_service;
+mod beacon_node_fallback;
 mod block_service;
 mod cli;
 mod config;
 mod duties_service;
 mod fork_service;
+mod health_service;
+mod http_api;
+mod metrics;
+mod slashing_protection;
 mod validator_store;
 
 pub mod validator_directory;
@@ -12,6 +17,7 @@ pub use cli::cli_app;
 pub use config::Config;
 
 use attestation_service::{AttestationService, AttestationServiceBuilder};
+use beacon_node_fallback::BeaconNodeFallback;
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
 use config::{Config as ClientConfig, KeySource};
@@ -23,11 +29,15 @@ use futures::{
     future::{self, loop_fn, Loop},
     Future, IntoFuture,
 };
+use health_service::{HealthService, HealthServiceBuilder, NodeHealth};
+use http_api::{ApiService, ApiServiceBuilder};
+use metrics::DutyMetrics;
 use parking_lot::RwLock;
 use remote_beacon_node::RemoteBeaconNode;
 use slog::{error, info, Logger};
 use slot_clock::SlotClock;
 use slot_clock::SystemTimeSlotClock;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::timer::Delay;
@@ -43,6 +53,9 @@ pub struct ProductionValidatorClient<T: EthSpec> {
     fork_service: ForkService<SystemTimeSlotClock, T>,
     block_service: BlockService<SystemTimeSlotClock, T>,
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
+    health_service: HealthService<SystemTimeSlotClock, T>,
+    api_service: Option<ApiService<T>>,
+    beacon_nodes: BeaconNodeFallback<T>,
     exit_signals: Arc<RwLock<Vec<Signal>>>,
 }
 
@@ -59,6 +72,13 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             .and_then(|client_config| Self::new(context, client_config))
     }
 
+    /// The current health of the connected beacon node, for logging or
+    /// exposing via an API. `None` until the first health check completes, and
+    /// after any health check in which no beacon node responded.
+    pub fn node_health(&self) -> Option<NodeHealth> {
+        self.health_service.health()
+    }
+
     /// Instantiates the validator client, _without_ starting the timers to trigger block
     /// and attestation production.
     pub fn new(
@@ -75,41 +95,42 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             "datadir" => format!("{:?}", client_config.data_dir),
         );
 
-        format!(
-            "{}:{}",
-            client_config.server, client_config.server_http_port
-        )
-        .parse()
-        .map_err(|e| format!("Unable to parse server address: {:?}", e))
-        .into_future()
-        .and_then(move |http_server_addr| {
-            info!(
-                log_1,
-                "Beacon node connection info";
-                "http_server" => format!("{}", http_server_addr),
-            );
-
-            RemoteBeaconNode::new(http_server_addr)
-                .map_err(|e| format!("Unable to init beacon node http client: {}", e))
-        })
-        .and_then(move |beacon_node| wait_for_node(beacon_node, log_2))
-        .and_then(|beacon_node| {
-            beacon_node
-                .http
-                .spec()
-                .get_eth2_config()
-                .map(|eth2_config| (beacon_node, eth2_config))
-                .map_err(|e| format!("Unable to read eth2 config from beacon node: {:?}", e))
-        })
-        .and_then(|(beacon_node, eth2_config)| {
-            beacon_node
-                .http
-                .beacon()
-                .get_genesis_time()
-                .map(|genesis_time| (beacon_node, eth2_config, genesis_time))
-                .map_err(|e| format!("Unable to read genesis time from beacon node: {:?}", e))
-        })
-        .and_then(move |(beacon_node, remote_eth2_config, genesis_time)| {
+        client_config
+            .beacon_nodes
+            .iter()
+            .map(|addr| {
+                addr.parse()
+                    .map_err(|e| format!("Unable to parse beacon node address {}: {:?}", addr, e))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .into_future()
+            .and_then(move |http_server_addrs: Vec<_>| {
+                info!(
+                    log_1,
+                    "Beacon node connection info";
+                    "http_servers" => format!("{:?}", http_server_addrs),
+                );
+
+                http_server_addrs
+                    .into_iter()
+                    .map(RemoteBeaconNode::new)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Unable to init beacon node http client: {}", e))
+            })
+            .and_then(move |beacon_nodes| wait_for_any_node(beacon_nodes, log_2))
+            .and_then(|beacon_nodes| {
+                beacon_nodes
+                    .first_success(|beacon_node| beacon_node.http.spec().get_eth2_config())
+                    .map(|eth2_config| (beacon_nodes, eth2_config))
+                    .map_err(|e| format!("Unable to read eth2 config from beacon node: {:?}", e))
+            })
+            .and_then(|(beacon_nodes, eth2_config)| {
+                beacon_nodes
+                    .first_success(|beacon_node| beacon_node.http.beacon().get_genesis_time())
+                    .map(|genesis_time| (beacon_nodes, eth2_config, genesis_time))
+                    .map_err(|e| format!("Unable to read genesis time from beacon node: {:?}", e))
+            })
+            .and_then(move |(beacon_nodes, remote_eth2_config, genesis_time)| {
             // Do not permit a connection to a beacon node using different spec constants.
             if context.eth2_config.spec_constants != remote_eth2_config.spec_constants {
                 return Err(format!(
@@ -160,49 +181,100 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                 "voting_validators" => validator_store.num_voting_validators()
             );
 
+            if let Some(path) = &client_config.import_slashing_protection {
+                let file = std::fs::File::open(path)
+                    .map_err(|e| format!("Unable to open slashing protection interchange: {:?}", e))?;
+                let interchange = serde_json::from_reader(file)
+                    .map_err(|e| format!("Unable to parse slashing protection interchange: {:?}", e))?;
+                validator_store
+                    .import_slashing_protection(interchange)
+                    .map_err(|e| format!("Unable to import slashing protection interchange: {:?}", e))?;
+
+                info!(log_3, "Imported slashing protection interchange"; "path" => format!("{:?}", path));
+            }
+
             let duties_service = DutiesServiceBuilder::new()
                 .slot_clock(slot_clock.clone())
                 .validator_store(validator_store.clone())
-                .beacon_node(beacon_node.clone())
+                .beacon_node(beacon_nodes.clone())
                 .runtime_context(context.service_context("duties"))
                 .build()?;
 
             let fork_service = ForkServiceBuilder::new()
                 .slot_clock(slot_clock.clone())
-                .beacon_node(beacon_node.clone())
+                .beacon_node(beacon_nodes.clone())
                 .runtime_context(context.service_context("fork"))
                 .build()?;
 
+            let health_service = HealthServiceBuilder::new()
+                .slot_clock(slot_clock.clone())
+                .beacon_node(beacon_nodes.clone())
+                .tolerance_slots(client_config.health_tolerance_slots)
+                .runtime_context(context.service_context("health"))
+                .build()?;
+
+            let metrics = Arc::new(DutyMetrics::new());
+
             let block_service = BlockServiceBuilder::new()
                 .duties_service(duties_service.clone())
                 .fork_service(fork_service.clone())
+                .health_service(health_service.clone())
                 .slot_clock(slot_clock.clone())
                 .validator_store(validator_store.clone())
-                .beacon_node(beacon_node.clone())
+                .beacon_node(beacon_nodes.clone())
+                .metrics(metrics.clone())
                 .runtime_context(context.service_context("block"))
                 .build()?;
 
             let attestation_service = AttestationServiceBuilder::new()
                 .duties_service(duties_service.clone())
                 .fork_service(fork_service.clone())
-                .slot_clock(slot_clock)
-                .validator_store(validator_store)
-                .beacon_node(beacon_node)
+                .health_service(health_service.clone())
+                .slot_clock(slot_clock.clone())
+                .validator_store(validator_store.clone())
+                .beacon_node(beacon_nodes.clone())
+                .metrics(metrics.clone())
                 .runtime_context(context.service_context("attestation"))
                 .build()?;
 
+            let api_service = if client_config.http_api_enabled {
+                let listen_addr =
+                    SocketAddr::new(client_config.http_api_listen_addr, client_config.http_api_port);
+
+                Some(
+                    ApiServiceBuilder::new()
+                        .listen_addr(listen_addr)
+                        .metrics(metrics)
+                        .health_service(health_service.clone())
+                        .beacon_nodes(beacon_nodes.clone())
+                        .validator_store(validator_store)
+                        .slot_clock(slot_clock)
+                        .runtime_context(context.service_context("http_api"))
+                        .build()?,
+                )
+            } else {
+                None
+            };
+
             Ok(Self {
                 context,
                 duties_service,
                 fork_service,
+                health_service,
                 block_service,
                 attestation_service,
+                api_service,
+                beacon_nodes,
                 exit_signals: Arc::new(RwLock::new(vec![])),
             })
         })
     }
 
     pub fn start_service(&self) -> Result<(), String> {
+        self.exit_signals
+            .write()
+            .push(self.beacon_nodes.start_update_service());
+
         let duties_exit = self
             .duties_service
             .start_update_service(&self.context.eth2_config.spec)
@@ -217,6 +289,13 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
 
         self.exit_signals.write().push(fork_exit);
 
+        let health_exit = self
+            .health_service
+            .start_update_service(&self.context.eth2_config.spec)
+            .map_err(|e| format!("Unable to start health service: {}", e))?;
+
+        self.exit_signals.write().push(health_exit);
+
         let block_exit = self
             .block_service
             .start_update_service(&self.context.eth2_config.spec)
@@ -231,54 +310,62 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
 
         self.exit_signals.write().push(attestation_exit);
 
+        if let Some(api_service) = &self.api_service {
+            let api_exit = api_service
+                .start_update_service()
+                .map_err(|e| format!("Unable to start HTTP API: {}", e))?;
+
+            self.exit_signals.write().push(api_exit);
+        }
+
         Ok(())
     }
 }
 
-/// Request the version from the node, looping back and trying again on failure. Exit once the node
-/// has been contacted.
-fn wait_for_node<E: EthSpec>(
-    beacon_node: RemoteBeaconNode<E>,
+/// Probe every configured beacon node in order, looping back and trying the
+/// whole list again on failure. Exits once *at least one* node has been
+/// successfully contacted, wrapping the full list in a `BeaconNodeFallback` so
+/// callers transparently fail over to the next node if the first goes down.
+fn wait_for_any_node<E: EthSpec>(
+    beacon_nodes: Vec<RemoteBeaconNode<E>>,
     log: Logger,
-) -> impl Future<Item = RemoteBeaconNode<E>, Error = String> {
-    // Try to get the version string from the node, looping until success is returned.
-    loop_fn(beacon_node.clone(), move |beacon_node| {
+) -> impl Future<Item = BeaconNodeFallback<E>, Error = String> {
+    let fallback_log = log.clone();
+
+    loop_fn(beacon_nodes, move |beacon_nodes| {
         let log = log.clone();
-        beacon_node
-            .clone()
-            .http
-            .node()
-            .get_version()
-            .map_err(|e| format!("{:?}", e))
-            .then(move |result| {
-                let future: Box<dyn Future<Item = Loop<_, _>, Error = String> + Send> = match result
-                {
-                    Ok(version) => {
-                        info!(
-                            log,
-                            "Connected to beacon node";
-                            "version" => version,
-                        );
-
-                        Box::new(future::ok(Loop::Break(beacon_node)))
-                    }
-                    Err(e) => {
-                        error!(
-                            log,
-                            "Unable to connect to beacon node";
-                            "error" => format!("{:?}", e),
-                        );
-
-                        Box::new(
-                            Delay::new(Instant::now() + RETRY_DELAY)
-                                .map_err(|e| format!("Failed to trigger delay: {:?}", e))
-                                .and_then(|_| future::ok(Loop::Continue(beacon_node))),
-                        )
-                    }
-                };
-
-                future
-            })
+
+        future::join_all(beacon_nodes.iter().cloned().enumerate().map(
+            move |(i, beacon_node)| {
+                beacon_node
+                    .http
+                    .node()
+                    .get_version()
+                    .map(move |version| (i, Some(version)))
+                    .or_else(move |_| future::ok((i, None)))
+            },
+        ))
+        .and_then(move |results| {
+            let any_reachable = results.iter().any(|(_, version)| version.is_some());
+
+            for (i, version) in &results {
+                match version {
+                    Some(version) => info!(log, "Connected to beacon node"; "node_index" => i, "version" => version),
+                    None => error!(log, "Unable to connect to beacon node"; "node_index" => i),
+                }
+            }
+
+            if any_reachable {
+                Box::new(future::ok(Loop::Break(beacon_nodes)))
+                    as Box<dyn Future<Item = Loop<_, _>, Error = String> + Send>
+            } else {
+                Box::new(
+                    Delay::new(Instant::now() + RETRY_DELAY)
+                        .map_err(|e| format!("Failed to trigger delay: {:?}", e))
+                        .and_then(|_| future::ok(Loop::Continue(beacon_nodes))),
+                )
+            }
+        })
     })
-    .map(|_| beacon_node)
+    .map(move |beacon_nodes| BeaconNodeFallback::new(beacon_nodes, fallback_log))
 }
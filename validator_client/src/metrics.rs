@@ -0,0 +1,196 @@
+//! Per-validator duty counters, rendered in Prometheus text format by the
+//! optional HTTP API so operators can alert on missed duties.
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use types::PublicKey;
+
+#[derive(Default)]
+struct ValidatorCounters {
+    blocks_produced: AtomicU64,
+    blocks_failed: AtomicU64,
+    attestations_produced: AtomicU64,
+    attestations_failed: AtomicU64,
+}
+
+/// Tracks, per validator, how many blocks/attestations have been signed or
+/// refused by `ValidatorStore` (e.g. due to a slashing-protection rejection).
+/// Duties skipped by the health gate or for want of a known fork are not
+/// counted.
+#[derive(Default)]
+pub struct DutyMetrics {
+    per_validator: RwLock<HashMap<PublicKey, ValidatorCounters>>,
+}
+
+impl DutyMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_block_produced(&self, pubkey: &PublicKey) {
+        self.counters(pubkey)
+            .blocks_produced
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_block_failed(&self, pubkey: &PublicKey) {
+        self.counters(pubkey)
+            .blocks_failed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_attestation_produced(&self, pubkey: &PublicKey) {
+        self.counters(pubkey)
+            .attestations_produced
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_attestation_failed(&self, pubkey: &PublicKey) {
+        self.counters(pubkey)
+            .attestations_failed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(
+        &self,
+        pubkey: &PublicKey,
+    ) -> parking_lot::MappedRwLockReadGuard<ValidatorCounters> {
+        if !self.per_validator.read().contains_key(pubkey) {
+            self.per_validator
+                .write()
+                .entry(pubkey.clone())
+                .or_insert_with(ValidatorCounters::default);
+        }
+
+        parking_lot::RwLockReadGuard::map(self.per_validator.read(), |map| {
+            map.get(pubkey).expect("just inserted above")
+        })
+    }
+
+    /// Render all counters as Prometheus exposition-format text.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        for (pubkey, counters) in self.per_validator.read().iter() {
+            let label = format!("{:?}", pubkey);
+
+            let _ = writeln!(
+                out,
+                "validator_blocks_produced_total{{pubkey=\"{}\"}} {}",
+                label,
+                counters.blocks_produced.load(Ordering::Relaxed)
+            );
+            let _ = writeln!(
+                out,
+                "validator_blocks_failed_total{{pubkey=\"{}\"}} {}",
+                label,
+                counters.blocks_failed.load(Ordering::Relaxed)
+            );
+            let _ = writeln!(
+                out,
+                "validator_attestations_produced_total{{pubkey=\"{}\"}} {}",
+                label,
+                counters.attestations_produced.load(Ordering::Relaxed)
+            );
+            let _ = writeln!(
+                out,
+                "validator_attestations_failed_total{{pubkey=\"{}\"}} {}",
+                label,
+                counters.attestations_failed.load(Ordering::Relaxed)
+            );
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Keypair;
+
+    fn random_pubkey() -> PublicKey {
+        Keypair::random().pk
+    }
+
+    #[test]
+    fn counters_start_at_zero() {
+        let metrics = DutyMetrics::new();
+        let pubkey = random_pubkey();
+        let label = format!("{:?}", pubkey);
+
+        metrics.record_block_produced(&pubkey);
+
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains(&format!(
+            "validator_blocks_produced_total{{pubkey=\"{}\"}} 1",
+            label
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_blocks_failed_total{{pubkey=\"{}\"}} 0",
+            label
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_attestations_produced_total{{pubkey=\"{}\"}} 0",
+            label
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_attestations_failed_total{{pubkey=\"{}\"}} 0",
+            label
+        )));
+    }
+
+    #[test]
+    fn records_each_counter_independently() {
+        let metrics = DutyMetrics::new();
+        let pubkey = random_pubkey();
+        let label = format!("{:?}", pubkey);
+
+        metrics.record_block_produced(&pubkey);
+        metrics.record_block_produced(&pubkey);
+        metrics.record_block_failed(&pubkey);
+        metrics.record_attestation_produced(&pubkey);
+        metrics.record_attestation_failed(&pubkey);
+        metrics.record_attestation_failed(&pubkey);
+
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains(&format!(
+            "validator_blocks_produced_total{{pubkey=\"{}\"}} 2",
+            label
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_blocks_failed_total{{pubkey=\"{}\"}} 1",
+            label
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_attestations_produced_total{{pubkey=\"{}\"}} 1",
+            label
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_attestations_failed_total{{pubkey=\"{}\"}} 2",
+            label
+        )));
+    }
+
+    #[test]
+    fn tracks_separate_validators_independently() {
+        let metrics = DutyMetrics::new();
+        let a = random_pubkey();
+        let b = random_pubkey();
+
+        metrics.record_block_produced(&a);
+        metrics.record_block_failed(&b);
+
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains(&format!(
+            "validator_blocks_produced_total{{pubkey=\"{:?}\"}} 1",
+            a
+        )));
+        assert!(rendered.contains(&format!(
+            "validator_blocks_failed_total{{pubkey=\"{:?}\"}} 1",
+            b
+        )));
+    }
+}
@@ -0,0 +1,341 @@
+//! A local, SQLite-backed slashing-protection database.
+//!
+//! Records the highest signed block slot, and the full history of signed
+//! (source epoch, target epoch) attestation pairs, per validator public key.
+//! Rejects any signing request that would violate the two slashing
+//! conditions for attestations (double vote, surround vote) or the single
+//! slashing condition for blocks (two different blocks at the same slot).
+//!
+//! A new attestation is checked against *every* previously signed pair for
+//! the validator, not an aggregate bound, since a lossy min/max summary of
+//! the signing history cannot soundly detect every surrounding vote.
+//!
+//! Re-signing the exact same (source, target) pair already on record is
+//! permitted — it's a retry (e.g. after a network hiccup between signing and
+//! publishing), not a slashing condition — and is a no-op rather than a fresh
+//! insert.
+//!
+//! Every check-and-record pair runs while holding the connection lock, so a
+//! crash between signing and persisting cannot reopen a slashing window.
+
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use types::{Epoch, PublicKey, Slot};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NotSafe {
+    /// A block has already been signed at this slot, or a later one.
+    SlashableBlock {
+        existing_slot: Slot,
+    },
+    /// The attestation double-votes an existing target epoch.
+    DoubleVote,
+    /// The attestation surrounds, or is surrounded by, a previously signed one.
+    SurroundVote,
+    DatabaseError(String),
+}
+
+impl From<rusqlite::Error> for NotSafe {
+    fn from(e: rusqlite::Error) -> Self {
+        NotSafe::DatabaseError(format!("{:?}", e))
+    }
+}
+
+/// A single validator's slashing-protection history, as recorded in an
+/// interchange file produced by another client.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InterchangeRecord {
+    pub pubkey: PublicKey,
+    pub last_signed_block_slot: Option<Slot>,
+    pub last_signed_attestation_source_epoch: Option<Epoch>,
+    pub last_signed_attestation_target_epoch: Option<Epoch>,
+}
+
+/// A set of `InterchangeRecord`s, as imported from another client's export.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Interchange {
+    pub records: Vec<InterchangeRecord>,
+}
+
+/// Persistent, atomic slashing-protection database for a set of validators.
+pub struct SlashingDatabase {
+    conn: Mutex<Connection>,
+}
+
+impl SlashingDatabase {
+    /// Open the database at `path`, creating it (and its tables) if it does not exist.
+    pub fn open_or_create(path: &Path) -> Result<Self, NotSafe> {
+        let conn = Connection::open(path)?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS signed_blocks (
+                validator_pubkey TEXT NOT NULL PRIMARY KEY,
+                slot INTEGER NOT NULL
+            )",
+            params![],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS signed_attestations (
+                validator_pubkey TEXT NOT NULL,
+                source_epoch INTEGER NOT NULL,
+                target_epoch INTEGER NOT NULL
+            )",
+            params![],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_signed_attestations_pubkey
+             ON signed_attestations (validator_pubkey)",
+            params![],
+        )?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Check that signing a block at `slot` for `pubkey` does not double-propose
+    /// or propose at a slot prior to the one last signed, and atomically record
+    /// the new slot if the check passes.
+    pub fn check_and_insert_block_proposal(
+        &self,
+        pubkey: &PublicKey,
+        slot: Slot,
+    ) -> Result<(), NotSafe> {
+        let conn = self.conn.lock();
+        let pubkey_str = format!("{:?}", pubkey);
+
+        let existing_slot: Option<u64> = conn
+            .query_row(
+                "SELECT slot FROM signed_blocks WHERE validator_pubkey = ?1",
+                params![pubkey_str],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if let Some(existing_slot) = existing_slot {
+            let existing_slot = Slot::new(existing_slot);
+            if slot <= existing_slot {
+                return Err(NotSafe::SlashableBlock { existing_slot });
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO signed_blocks (validator_pubkey, slot) VALUES (?1, ?2)
+             ON CONFLICT(validator_pubkey) DO UPDATE SET slot = excluded.slot",
+            params![pubkey_str, slot.as_u64()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Check that signing an attestation with `(source_epoch, target_epoch)` for
+    /// `pubkey` does not double-vote, surround, or get surrounded by, any
+    /// attestation this validator has previously signed, and atomically record
+    /// the new pair if the check passes.
+    pub fn check_and_insert_attestation(
+        &self,
+        pubkey: &PublicKey,
+        source_epoch: Epoch,
+        target_epoch: Epoch,
+    ) -> Result<(), NotSafe> {
+        let conn = self.conn.lock();
+        let pubkey_str = format!("{:?}", pubkey);
+
+        let existing: Vec<(u64, u64)> = {
+            let mut stmt = conn.prepare(
+                "SELECT source_epoch, target_epoch FROM signed_attestations
+                 WHERE validator_pubkey = ?1",
+            )?;
+
+            stmt.query_map(params![pubkey_str], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        for (existing_source, existing_target) in existing {
+            let existing_source = Epoch::new(existing_source);
+            let existing_target = Epoch::new(existing_target);
+
+            // Re-signing the exact same attestation data is not slashable —
+            // it's a retry, not a double vote — so let it through without
+            // inserting a duplicate row.
+            if source_epoch == existing_source && target_epoch == existing_target {
+                return Ok(());
+            }
+            if target_epoch == existing_target {
+                return Err(NotSafe::DoubleVote);
+            }
+            if source_epoch < existing_source && target_epoch > existing_target {
+                return Err(NotSafe::SurroundVote);
+            }
+            if source_epoch > existing_source && target_epoch < existing_target {
+                return Err(NotSafe::SurroundVote);
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO signed_attestations (validator_pubkey, source_epoch, target_epoch)
+             VALUES (?1, ?2, ?3)",
+            params![pubkey_str, source_epoch.as_u64(), target_epoch.as_u64()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Import an interchange of slashing-protection history from another client,
+    /// so that validators migrating to Lighthouse do not get slashed by replaying
+    /// duties they have already performed elsewhere.
+    pub fn import_interchange(&self, interchange: Interchange) -> Result<(), NotSafe> {
+        for record in interchange.records {
+            let conn = self.conn.lock();
+            let pubkey_str = format!("{:?}", record.pubkey);
+
+            if let Some(slot) = record.last_signed_block_slot {
+                conn.execute(
+                    "INSERT INTO signed_blocks (validator_pubkey, slot) VALUES (?1, ?2)
+                     ON CONFLICT(validator_pubkey) DO UPDATE SET
+                        slot = MAX(slot, excluded.slot)",
+                    params![pubkey_str, slot.as_u64()],
+                )?;
+            }
+
+            if let (Some(source), Some(target)) = (
+                record.last_signed_attestation_source_epoch,
+                record.last_signed_attestation_target_epoch,
+            ) {
+                conn.execute(
+                    "INSERT INTO signed_attestations (validator_pubkey, source_epoch, target_epoch)
+                     VALUES (?1, ?2, ?3)",
+                    params![pubkey_str, source.as_u64(), target.as_u64()],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Keypair;
+
+    fn new_db() -> SlashingDatabase {
+        SlashingDatabase::open_or_create(Path::new(":memory:")).expect("in-memory db opens")
+    }
+
+    fn random_pubkey() -> PublicKey {
+        Keypair::random().pk
+    }
+
+    #[test]
+    fn rejects_non_increasing_block_slot() {
+        let db = new_db();
+        let pubkey = random_pubkey();
+
+        db.check_and_insert_block_proposal(&pubkey, Slot::new(10))
+            .expect("first block at a slot is always safe");
+
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, Slot::new(10)),
+            Err(NotSafe::SlashableBlock {
+                existing_slot: Slot::new(10)
+            })
+        );
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, Slot::new(9)),
+            Err(NotSafe::SlashableBlock {
+                existing_slot: Slot::new(10)
+            })
+        );
+
+        db.check_and_insert_block_proposal(&pubkey, Slot::new(11))
+            .expect("a strictly later slot is safe");
+    }
+
+    #[test]
+    fn rejects_double_vote() {
+        let db = new_db();
+        let pubkey = random_pubkey();
+
+        db.check_and_insert_attestation(&pubkey, Epoch::new(1), Epoch::new(2))
+            .expect("first attestation is always safe");
+
+        // Same target epoch, different source: a genuine double vote.
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, Epoch::new(0), Epoch::new(2)),
+            Err(NotSafe::DoubleVote)
+        );
+    }
+
+    #[test]
+    fn allows_resigning_the_exact_same_attestation() {
+        let db = new_db();
+        let pubkey = random_pubkey();
+
+        db.check_and_insert_attestation(&pubkey, Epoch::new(1), Epoch::new(2))
+            .expect("first attestation is always safe");
+
+        // Identical (source, target) is a retry, not a slashing condition.
+        db.check_and_insert_attestation(&pubkey, Epoch::new(1), Epoch::new(2))
+            .expect("re-signing identical attestation data is not slashable");
+    }
+
+    #[test]
+    fn rejects_surrounding_vote() {
+        let db = new_db();
+        let pubkey = random_pubkey();
+
+        db.check_and_insert_attestation(&pubkey, Epoch::new(10), Epoch::new(12))
+            .expect("first attestation is always safe");
+
+        // (5, 20) surrounds (10, 12): 5 < 10 and 20 > 12.
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, Epoch::new(5), Epoch::new(20)),
+            Err(NotSafe::SurroundVote)
+        );
+    }
+
+    #[test]
+    fn rejects_surrounded_vote() {
+        let db = new_db();
+        let pubkey = random_pubkey();
+
+        db.check_and_insert_attestation(&pubkey, Epoch::new(1), Epoch::new(20))
+            .expect("first attestation is always safe");
+
+        // (10, 12) is surrounded by (1, 20): 1 < 10 and 20 > 12.
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, Epoch::new(10), Epoch::new(12)),
+            Err(NotSafe::SurroundVote)
+        );
+    }
+
+    #[test]
+    fn accepts_legitimate_non_overlapping_votes() {
+        let db = new_db();
+        let pubkey = random_pubkey();
+
+        db.check_and_insert_attestation(&pubkey, Epoch::new(1), Epoch::new(2))
+            .expect("first attestation is always safe");
+        db.check_and_insert_attestation(&pubkey, Epoch::new(2), Epoch::new(3))
+            .expect("a later, non-overlapping vote is safe");
+
+        // Also guard against the aggregate-bound regression directly: after
+        // signing (10, 12) then (1, 2), a naive min-source/max-target
+        // aggregate of (1, 12) would wrongly accept (5, 20), which actually
+        // surrounds (10, 12).
+        let other_pubkey = random_pubkey();
+        db.check_and_insert_attestation(&other_pubkey, Epoch::new(10), Epoch::new(12))
+            .expect("first attestation is always safe");
+        db.check_and_insert_attestation(&other_pubkey, Epoch::new(1), Epoch::new(2))
+            .expect("does not surround or get surrounded by (10, 12)");
+        assert_eq!(
+            db.check_and_insert_attestation(&other_pubkey, Epoch::new(5), Epoch::new(20)),
+            Err(NotSafe::SurroundVote)
+        );
+    }
+}
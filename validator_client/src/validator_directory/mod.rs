@@ -0,0 +1,42 @@
+//! Reads the on-disk directory layout produced by the `account_manager`: one
+//! sub-directory per validator, each holding a voting keystore.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use types::Keypair;
+
+#[derive(Debug)]
+pub enum Error {
+    UnableToReadDir(String),
+    UnableToReadKeypair(PathBuf, String),
+}
+
+pub struct ValidatorDirectory {
+    pub directory: PathBuf,
+    pub keypair: Keypair,
+}
+
+impl ValidatorDirectory {
+    /// Load every validator sub-directory of `base_dir`.
+    pub fn load_all(base_dir: &Path) -> Result<Vec<Self>, Error> {
+        let entries =
+            fs::read_dir(base_dir).map_err(|e| Error::UnableToReadDir(format!("{:?}", e)))?;
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| Self::load(entry.path()))
+            .collect()
+    }
+
+    fn load(directory: PathBuf) -> Result<Self, Error> {
+        let keypair_path = directory.join("voting_keypair");
+        let keypair =
+            Keypair::from_bytes(&fs::read(&keypair_path).map_err(|e| {
+                Error::UnableToReadKeypair(keypair_path.clone(), format!("{:?}", e))
+            })?)
+            .map_err(|e| Error::UnableToReadKeypair(keypair_path, format!("{:?}", e)))?;
+
+        Ok(Self { directory, keypair })
+    }
+}
@@ -0,0 +1,169 @@
+use crate::slashing_protection::{NotSafe, SlashingDatabase};
+use crate::validator_directory::ValidatorDirectory;
+use parking_lot::RwLock;
+use slog::{error, Logger};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use types::{
+    AggregateSignature, Attestation, BeaconBlock, ChainSpec, Domain, EthSpec, Fork, Keypair,
+    PublicKey, Signature,
+};
+
+const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.sqlite";
+
+#[derive(Clone)]
+pub struct ValidatorStore<T> {
+    validators: Arc<RwLock<HashMap<PublicKey, Keypair>>>,
+    slashing_protection: Arc<SlashingDatabase>,
+    spec: Arc<ChainSpec>,
+    log: Logger,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: EthSpec> ValidatorStore<T> {
+    /// Load validator keypairs (and open/create their slashing-protection
+    /// database) from `validator_directory`'s data on disk.
+    pub fn load_from_disk(data_dir: PathBuf, spec: ChainSpec, log: Logger) -> Result<Self, String> {
+        let validators = ValidatorDirectory::load_all(&data_dir)
+            .map_err(|e| format!("Unable to load validator directories: {:?}", e))?
+            .into_iter()
+            .map(|directory| (directory.keypair.pk.clone(), directory.keypair))
+            .collect();
+
+        let slashing_protection =
+            SlashingDatabase::open_or_create(&data_dir.join(SLASHING_PROTECTION_FILENAME))
+                .map_err(|e| format!("Unable to open slashing protection database: {:?}", e))?;
+
+        Ok(Self {
+            validators: Arc::new(RwLock::new(validators)),
+            slashing_protection: Arc::new(slashing_protection),
+            spec: Arc::new(spec),
+            log,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Generate ephemeral, insecure keypairs for testing. The accompanying
+    /// slashing-protection database is created in-memory.
+    ///
+    /// Do not use in production.
+    pub fn insecure_ephemeral_validators(
+        range: Range<usize>,
+        spec: ChainSpec,
+        log: Logger,
+    ) -> Result<Self, String> {
+        let validators = range
+            .map(|index| {
+                let keypair = Keypair::from_bytes(&vec![index as u8; 32])
+                    .unwrap_or_else(|_| Keypair::random());
+                (keypair.pk.clone(), keypair)
+            })
+            .collect();
+
+        // SQLite only opens an in-memory database when the path is the
+        // literal string `:memory:` — anything else (even a `:memory:`
+        // prefix) is just a regular filename.
+        let slashing_protection = SlashingDatabase::open_or_create(Path::new(":memory:"))
+            .map_err(|e| format!("Unable to open slashing protection database: {:?}", e))?;
+
+        Ok(Self {
+            validators: Arc::new(RwLock::new(validators)),
+            slashing_protection: Arc::new(slashing_protection),
+            spec: Arc::new(spec),
+            log,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    pub fn num_voting_validators(&self) -> usize {
+        self.validators.read().len()
+    }
+
+    pub fn voting_pubkeys(&self) -> Vec<PublicKey> {
+        self.validators.read().keys().cloned().collect()
+    }
+
+    /// Sign `block` for `validator_pubkey`, refusing (and logging) if doing so
+    /// would violate the block slashing condition: two different blocks at the
+    /// same, or a non-increasing, slot.
+    pub fn sign_block(
+        &self,
+        validator_pubkey: &PublicKey,
+        mut block: BeaconBlock<T>,
+        fork: &Fork,
+    ) -> Option<BeaconBlock<T>> {
+        let validators = self.validators.read();
+        let keypair = validators.get(validator_pubkey)?;
+
+        if let Err(e) = self
+            .slashing_protection
+            .check_and_insert_block_proposal(validator_pubkey, block.slot)
+        {
+            error!(
+                self.log,
+                "Refusing to sign slashable block";
+                "validator_pubkey" => format!("{:?}", validator_pubkey),
+                "reason" => format!("{:?}", e),
+            );
+            return None;
+        }
+
+        let domain = self.spec.get_domain(
+            block.slot.epoch(T::slots_per_epoch()),
+            Domain::BeaconProposer,
+            fork,
+        );
+        block.signature = Signature::new(&block.signing_root(domain).as_bytes(), &keypair.sk);
+
+        Some(block)
+    }
+
+    /// Sign `attestation` for `validator_pubkey`, refusing (and logging) if doing
+    /// so would double-vote or surround-vote against a previously signed
+    /// attestation for this validator.
+    pub fn sign_attestation(
+        &self,
+        validator_pubkey: &PublicKey,
+        attestation: &mut Attestation<T>,
+        fork: &Fork,
+    ) -> Option<()> {
+        let validators = self.validators.read();
+        let keypair = validators.get(validator_pubkey)?;
+
+        let source_epoch = attestation.data.source.epoch;
+        let target_epoch = attestation.data.target.epoch;
+
+        if let Err(e) = self.slashing_protection.check_and_insert_attestation(
+            validator_pubkey,
+            source_epoch,
+            target_epoch,
+        ) {
+            error!(
+                self.log,
+                "Refusing to sign slashable attestation";
+                "validator_pubkey" => format!("{:?}", validator_pubkey),
+                "reason" => format!("{:?}", e),
+            );
+            return None;
+        }
+
+        let domain = self.spec.get_domain(target_epoch, Domain::BeaconAttester, fork);
+        let message = attestation.data.signing_root(domain);
+        attestation.signature =
+            AggregateSignature::from(Signature::new(&message.as_bytes(), &keypair.sk));
+
+        Some(())
+    }
+
+    /// Import a slashing-protection interchange exported by another client, so
+    /// that migrating validators are not at risk of signing a duty they have
+    /// already performed elsewhere.
+    pub fn import_slashing_protection(
+        &self,
+        interchange: crate::slashing_protection::Interchange,
+    ) -> Result<(), NotSafe> {
+        self.slashing_protection.import_interchange(interchange)
+    }
+}